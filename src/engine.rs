@@ -6,104 +6,191 @@
 //                                                                          |
 //==========================================================================|
 
-use crate::map::{MAP_HEIGHT, MAP_WIDTH, WORLD_MAP};
+use crate::input::{GameAction, InputMap, InputState};
+use crate::map::Map;
+use crate::netcode::{NetcodeConfig, RollbackSession};
 use crate::player::Player;
 use crate::raycaster::cast_ray;
+use crate::sim::{self, GameState, PlayerInput, PlayerState};
+use crate::sprite::{draw_sprites, Sprite};
 use raylib::prelude::*;
+use std::error::Error;
 
-fn collision_check(circle_pos: Vector2, radius: f32, rect: Rectangle) -> bool {
-    let closest_x = circle_pos.x.clamp(rect.x, rect.x + rect.width);
-    let closest_y = circle_pos.y.clamp(rect.y, rect.y + rect.height);
-
-    let dx = circle_pos.x - closest_x;
-    let dy = circle_pos.y - closest_y;
-
-    dx * dx + dy * dy < radius * radius
+/// What column `x`'s wall draw needs, computed during raycasting and drawn
+/// afterwards so the floor/ceiling texture can be blitted underneath it in
+/// one call instead of interleaving per-column draws with per-pixel ones.
+struct WallColumn {
+    tex_x: i32,
+    draw_start: f32,
+    height: f32,
 }
 
-fn is_colliding(pos: Vector2, radius: f32) -> bool {
-    (0..MAP_HEIGHT).any(|y| {
-        (0..MAP_WIDTH).any(|x| {
-            WORLD_MAP[y][x] != 0
-                && collision_check(pos, radius, Rectangle::new(x as f32, y as f32, 1.0, 1.0))
+pub fn run_game(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    netcode: Option<NetcodeConfig>,
+) -> Result<(), Box<dyn Error>> {
+    let map = Map::load("assets/maps/level1.map")?;
+    let mut player = Player::new();
+    player.pos = map.spawn;
+    player.dir = map.spawn_dir.normalized();
+    player.plane = Vector2::new(-player.dir.y, player.dir.x) * 0.5;
+
+    let local_player = netcode.as_ref().map_or(0, |cfg| cfg.local_player);
+    let player_count = if netcode.is_some() { 2 } else { 1 };
+    let initial_state = GameState::new(vec![
+        PlayerState::new(player.pos, player.dir, player.plane);
+        player_count
+    ]);
+    let mut session = netcode
+        .map(|cfg| {
+            RollbackSession::new(
+                cfg.bind_addr,
+                cfg.peer_addr,
+                cfg.local_player,
+                1 - cfg.local_player,
+                cfg.input_delay,
+                initial_state.clone(),
+            )
         })
-    })
-}
+        .transpose()?;
+    let mut solo_state = initial_state;
 
-pub fn run_game(rl: &mut RaylibHandle, thread: &RaylibThread) {
-    let mut player = Player::new();
     let wall_texture = rl
         .load_texture(thread, "assets/wall.png")
         .expect("Missing Texture");
     wall_texture.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_POINT);
 
-    let ceiling_color = Color::new(20, 20, 30, 255);
-    let floor_color = Color::new(40, 30, 20, 255);
+    let sprite_texture = rl
+        .load_texture(thread, "assets/sprite.png")
+        .expect("Missing Texture");
+    let sprites = vec![Sprite {
+        pos: map.spawn + map.spawn_dir.normalized() * 3.0,
+        texture: &sprite_texture,
+    }];
+
+    let max_floor_tile = map.floor_tiles.iter().copied().max().unwrap_or(0);
+    let floor_images: Vec<Image> = (0..=max_floor_tile)
+        .map(|id| {
+            Image::load_image(&format!("assets/floor{id}.png")).expect("Missing floor texture")
+        })
+        .collect();
+    let ceiling_images: Vec<Image> = (0..=max_floor_tile)
+        .map(|id| {
+            Image::load_image(&format!("assets/ceiling{id}.png"))
+                .expect("Missing ceiling texture")
+        })
+        .collect();
 
     rl.set_target_fps(60);
     rl.disable_cursor();
 
     let (screen_w, screen_h) = (rl.get_screen_width(), rl.get_screen_height());
     let projected_plane = screen_w as f32 / 2.0;
-    const MOUSE_SENSITIVITY: f32 = 0.005;
-    const COLLISION_RADIUS: f32 = 0.1;
+
+    // Floor/ceiling casting fills this CPU buffer pixel-by-pixel, then
+    // uploads it to the GPU as a single texture per frame — one draw call
+    // instead of one `draw_pixel` per screen pixel.
+    let mut floor_ceiling_pixels = vec![0u8; (screen_w * screen_h * 4) as usize];
+    let mut floor_ceiling_texture = rl
+        .load_texture_from_image(thread, &Image::gen_image_color(screen_w, screen_h, Color::BLACK))
+        .expect("Failed to create floor/ceiling texture");
+    const TURN_KEY_SPEED: f32 = 0.03;
+    const PITCH_SENSITIVITY: f32 = 4.0;
+    const GRAVITY: f32 = 0.015;
+    const JUMP_VELOCITY: f32 = 0.2;
+
+    let input_map = InputMap::load("assets/input.cfg").unwrap_or_else(|_| InputMap::default_bindings());
+    let mut input = InputState::default();
 
     while !rl.window_should_close() {
-        let forward = player.dir * player.move_speed;
-        let strafe = Vector2::new(-player.dir.y, player.dir.x) * player.move_speed;
-
-        // Movement
-        for key in [
-            (KeyboardKey::KEY_W, forward),
-            (KeyboardKey::KEY_S, -forward),
-            (KeyboardKey::KEY_A, strafe),
-            (KeyboardKey::KEY_D, -strafe),
-        ] {
-            if rl.is_key_down(key.0) {
-                let move_dir = key.1;
-                let try_pos = player.pos + move_dir;
+        input = InputState::capture(rl, &input_map, &input);
 
-                // Wall sliding
-                if !is_colliding(try_pos, COLLISION_RADIUS) {
-                    player.pos = try_pos;
-                } else {
-                    let x_only = Vector2::new(try_pos.x, player.pos.y);
-                    let y_only = Vector2::new(player.pos.x, try_pos.y);
-
-                    if !is_colliding(try_pos, COLLISION_RADIUS) {
-                        player.pos.x = x_only.x;
-                    }
-                    if !is_colliding(y_only, COLLISION_RADIUS) {
-                        player.pos.y = y_only.y;
-                    }
-                }
+        // Movement + turn are collected into a serialized `PlayerInput` and
+        // handed to the deterministic `sim::step`, which is the only thing
+        // allowed to move `player.pos/dir/plane` now — this is what makes
+        // the same input replayable during a netcode rollback.
+        let mut buttons = 0u8;
+        for (action, bit) in [
+            (GameAction::MoveForward, PlayerInput::FORWARD),
+            (GameAction::MoveBack, PlayerInput::BACK),
+            (GameAction::StrafeLeft, PlayerInput::STRAFE_LEFT),
+            (GameAction::StrafeRight, PlayerInput::STRAFE_RIGHT),
+            (GameAction::Jump, PlayerInput::JUMP),
+        ] {
+            if input.down(action) {
+                buttons |= bit;
             }
         }
 
-        // Rotation
-        let delta_x = -rl.get_mouse_delta().x;
-        player.rotate(delta_x * MOUSE_SENSITIVITY);
+        let mut turn = -rl.get_mouse_delta().x * input_map.mouse_sensitivity;
+        if input.down(GameAction::TurnLeft) {
+            turn -= TURN_KEY_SPEED;
+        }
+        if input.down(GameAction::TurnRight) {
+            turn += TURN_KEY_SPEED;
+        }
+        let local_input = PlayerInput::with_turn(buttons, turn);
 
-        let mut d = rl.begin_drawing(thread);
+        let local_state = match &mut session {
+            Some(session) => {
+                session.receive(&map)?;
+                &session.advance(local_input, &map).players[local_player]
+            }
+            None => {
+                solo_state = sim::step(&solo_state, &[local_input], &map);
+                &solo_state.players[0]
+            }
+        };
+        player.pos = local_state.pos;
+        player.dir = local_state.dir;
+        player.plane = local_state.plane;
 
-        d.clear_background(ceiling_color);
-        d.draw_rectangle(0, screen_h / 2, screen_w, screen_h / 2, floor_color);
+        // Look: vertical mouse delta tilts the camera, clamped to roughly
+        // a screen's worth of pixels either way.
+        let delta_y = -rl.get_mouse_delta().y;
+        player.pitch = (player.pitch + delta_y * PITCH_SENSITIVITY)
+            .clamp(-(screen_h as f32), screen_h as f32);
+
+        // Jump & gravity: vel_z integrates each frame, pos_z is clamped to
+        // the floor so the player only leaves the ground via a jump.
+        if input.pressed(GameAction::Jump) && player.is_grounded() {
+            player.vel_z = JUMP_VELOCITY;
+        }
+        player.vel_z -= GRAVITY;
+        player.pos_z = (player.pos_z + player.vel_z).max(0.0);
+        if player.is_grounded() {
+            player.vel_z = 0.0;
+        }
+
+        let horizon = screen_h as f32 / 2.0 + player.pitch;
+        let mut wall_bounds = vec![(horizon, horizon); screen_w as usize];
+        let mut zbuffer = vec![f32::INFINITY; screen_w as usize];
+        let mut wall_columns: Vec<Option<WallColumn>> = (0..screen_w).map(|_| None).collect();
 
         for x in 0..screen_w {
             let camera_x = 2.0 * x as f32 / screen_w as f32 - 1.0;
             let ray_dir = player.dir + player.plane * camera_x;
 
-            if let Some(hit) = cast_ray(player.pos, ray_dir) {
+            if let Some(hit) = cast_ray(&map, player.pos, ray_dir) {
                 let _ray_dir_norm = ray_dir.normalized();
                 let cos_angle = _ray_dir_norm.dot(player.dir);
                 let corrected_dist = (hit.distance / cos_angle).max(0.2);
                 let wall_height = projected_plane / corrected_dist;
+                let horizon_col = horizon - player.pos_z * projected_plane / corrected_dist;
+
+                // Short tiles (barriers, window slits, platforms) occupy
+                // only a slice of the full floor-to-ceiling wall height.
+                let (height_factor, offset_factor) = Map::wall_profile(hit.tile);
+                let slice_top =
+                    horizon_col + wall_height / 2.0 - (offset_factor + height_factor) * wall_height;
+                let slice_bottom = horizon_col + wall_height / 2.0 - offset_factor * wall_height;
 
-                let draw_start = (screen_h as f32 / 2.0 - wall_height / 2.0).max(0.0).floor();
-                let draw_end = (screen_h as f32 / 2.0 + wall_height / 2.0)
-                    .min(screen_h as f32)
-                    .ceil();
+                let draw_start = slice_top.max(0.0).floor();
+                let draw_end = slice_bottom.min(screen_h as f32).ceil();
                 let height = draw_end - draw_start;
+                wall_bounds[x as usize] = (draw_start, draw_end);
+                zbuffer[x as usize] = corrected_dist;
 
                 let mut wall_x = if hit.side == 0 {
                     hit.hit_pos.y
@@ -118,25 +205,101 @@ pub fn run_game(rl: &mut RaylibHandle, thread: &RaylibThread) {
                 }
                 tex_x = tex_x.clamp(0, wall_texture.width() - 1);
 
-                d.draw_texture_pro(
-                    &wall_texture,
-                    Rectangle {
-                        x: tex_x as f32,
-                        y: 0.0,
-                        width: 1.0,
-                        height: wall_texture.height() as f32,
-                    },
-                    Rectangle {
-                        x: x as f32,
-                        y: draw_start,
-                        width: 1.0,
-                        height,
-                    },
-                    Vector2::zero(),
-                    0.0,
-                    Color::WHITE,
-                );
+                wall_columns[x as usize] = Some(WallColumn {
+                    tex_x,
+                    draw_start,
+                    height,
+                });
+            }
+        }
+
+        // Floor/ceiling casting: walk each screen row's world-space floor
+        // coordinate from the left edge of the view frustum to the right,
+        // sampling the map's floor/ceiling texture for the tile underfoot,
+        // into `floor_ceiling_pixels` rather than issuing a `draw_pixel`
+        // call per pixel. `horizon` (mid-screen shifted by pitch) splits
+        // floor rows below it from ceiling rows above; pos_z nudges how
+        // far each row's distance reaches, same as the jump raising the
+        // camera.
+        let ray_dir_left = player.dir - player.plane;
+        let ray_dir_right = player.dir + player.plane;
+        let half_h = screen_h as f32 / 2.0;
+
+        let cast_row = |pixels: &mut [u8], y: i32, row_distance: f32, images: &[Image]| {
+            let step = (ray_dir_right - ray_dir_left) * row_distance / screen_w as f32;
+            let mut floor = player.pos + ray_dir_left * row_distance;
+
+            for x in 0..screen_w {
+                let (wall_start, wall_end) = wall_bounds[x as usize];
+                if (y as f32) > wall_start && (y as f32) < wall_end {
+                    floor += step;
+                    continue;
+                }
+                let cell_x = floor.x.floor() as i32;
+                let cell_y = floor.y.floor() as i32;
+                let tex_u = floor.x - floor.x.floor();
+                let tex_v = floor.y - floor.y.floor();
+                let tile = map.floor_tile(cell_x, cell_y) as usize;
+
+                let img = &images[tile.min(images.len() - 1)];
+                let tx = (tex_u * img.width() as f32) as i32;
+                let ty = (tex_v * img.height() as f32) as i32;
+                let color = img.get_color(tx, ty);
+
+                let idx = ((y * screen_w + x) * 4) as usize;
+                pixels[idx] = color.r;
+                pixels[idx + 1] = color.g;
+                pixels[idx + 2] = color.b;
+                pixels[idx + 3] = 255;
+
+                floor += step;
             }
+        };
+
+        let floor_start = (horizon.floor() as i32 + 1).clamp(0, screen_h);
+        for y in floor_start..screen_h {
+            let row_distance = (half_h + player.pos_z * projected_plane) / (y as f32 - horizon);
+            cast_row(&mut floor_ceiling_pixels, y, row_distance, &floor_images);
+        }
+        let ceiling_end = (horizon.floor() as i32).clamp(0, screen_h);
+        for y in 0..ceiling_end {
+            let row_distance = (half_h - player.pos_z * projected_plane) / (horizon - y as f32);
+            cast_row(&mut floor_ceiling_pixels, y, row_distance, &ceiling_images);
         }
+
+        floor_ceiling_texture.update_texture(&floor_ceiling_pixels);
+
+        let mut d = rl.begin_drawing(thread);
+
+        d.clear_background(map.ceiling_color);
+        d.draw_texture(&floor_ceiling_texture, 0, 0, Color::WHITE);
+
+        for (x, column) in wall_columns.iter().enumerate() {
+            let Some(column) = column else { continue };
+            d.draw_texture_pro(
+                &wall_texture,
+                Rectangle {
+                    x: column.tex_x as f32,
+                    y: 0.0,
+                    width: 1.0,
+                    height: wall_texture.height() as f32,
+                },
+                Rectangle {
+                    x: x as f32,
+                    y: column.draw_start,
+                    width: 1.0,
+                    height: column.height,
+                },
+                Vector2::zero(),
+                0.0,
+                Color::WHITE,
+            );
+        }
+
+        draw_sprites(
+            &mut d, &player, &sprites, &zbuffer, &wall_bounds, horizon, screen_w, screen_h,
+        );
     }
+
+    Ok(())
 }
@@ -0,0 +1,271 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A logical thing the player can do, independent of the physical device
+/// used to trigger it. `run_game` queries actions instead of raw keys so
+/// rebinding never touches gameplay code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameAction {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Jump,
+}
+
+impl GameAction {
+    pub const ALL: [GameAction; 7] = [
+        GameAction::MoveForward,
+        GameAction::MoveBack,
+        GameAction::StrafeLeft,
+        GameAction::StrafeRight,
+        GameAction::TurnLeft,
+        GameAction::TurnRight,
+        GameAction::Jump,
+    ];
+
+    fn config_key(self) -> &'static str {
+        match self {
+            GameAction::MoveForward => "move_forward",
+            GameAction::MoveBack => "move_back",
+            GameAction::StrafeLeft => "strafe_left",
+            GameAction::StrafeRight => "strafe_right",
+            GameAction::TurnLeft => "turn_left",
+            GameAction::TurnRight => "turn_right",
+            GameAction::Jump => "jump",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(err) => write!(f, "failed to read input config: {err}"),
+            InputError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<std::io::Error> for InputError {
+    fn from(err: std::io::Error) -> Self {
+        InputError::Io(err)
+    }
+}
+
+/// Binds each [`GameAction`] to zero or more keyboard keys, plus a mouse
+/// turn sensitivity. Gameplay code never sees a `KeyboardKey` directly.
+pub struct InputMap {
+    keys: HashMap<GameAction, Vec<KeyboardKey>>,
+    pub mouse_sensitivity: f32,
+}
+
+impl InputMap {
+    pub fn default_bindings() -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(GameAction::MoveForward, vec![KeyboardKey::KEY_W]);
+        keys.insert(GameAction::MoveBack, vec![KeyboardKey::KEY_S]);
+        keys.insert(GameAction::StrafeLeft, vec![KeyboardKey::KEY_A]);
+        keys.insert(GameAction::StrafeRight, vec![KeyboardKey::KEY_D]);
+        keys.insert(GameAction::TurnLeft, vec![KeyboardKey::KEY_LEFT]);
+        keys.insert(GameAction::TurnRight, vec![KeyboardKey::KEY_RIGHT]);
+        keys.insert(GameAction::Jump, vec![KeyboardKey::KEY_SPACE]);
+
+        InputMap {
+            keys,
+            mouse_sensitivity: 0.005,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputError> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parses the same `key: value` header style as the map files: one
+    /// binding or setting per line.
+    pub fn parse(text: &str) -> Result<Self, InputError> {
+        let mut map = Self::default_bindings();
+
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(InputError::Parse {
+                    line: line_no + 1,
+                    message: format!("expected `key: value`, got `{line}`"),
+                });
+            };
+            let key = key.trim();
+            let value = value.trim();
+
+            if key == "mouse_sensitivity" {
+                map.mouse_sensitivity = value.parse::<f32>().map_err(|_| InputError::Parse {
+                    line: line_no + 1,
+                    message: format!("invalid mouse_sensitivity `{value}`"),
+                })?;
+                continue;
+            }
+
+            let Some(action) = GameAction::ALL.iter().copied().find(|a| a.config_key() == key)
+            else {
+                return Err(InputError::Parse {
+                    line: line_no + 1,
+                    message: format!("unknown action `{key}`"),
+                });
+            };
+
+            let mut bound = Vec::new();
+            for name in value.split(',') {
+                bound.push(parse_key(name.trim(), line_no + 1)?);
+            }
+            map.keys.insert(action, bound);
+        }
+
+        Ok(map)
+    }
+
+    fn keys_for(&self, action: GameAction) -> &[KeyboardKey] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+}
+
+fn parse_key(name: &str, line: usize) -> Result<KeyboardKey, InputError> {
+    match name.to_ascii_uppercase().as_str() {
+        "W" => Ok(KeyboardKey::KEY_W),
+        "A" => Ok(KeyboardKey::KEY_A),
+        "S" => Ok(KeyboardKey::KEY_S),
+        "D" => Ok(KeyboardKey::KEY_D),
+        "SPACE" => Ok(KeyboardKey::KEY_SPACE),
+        "LEFT" => Ok(KeyboardKey::KEY_LEFT),
+        "RIGHT" => Ok(KeyboardKey::KEY_RIGHT),
+        "UP" => Ok(KeyboardKey::KEY_UP),
+        "DOWN" => Ok(KeyboardKey::KEY_DOWN),
+        "LEFT_SHIFT" => Ok(KeyboardKey::KEY_LEFT_SHIFT),
+        "LEFT_CONTROL" => Ok(KeyboardKey::KEY_LEFT_CONTROL),
+        other => Err(InputError::Parse {
+            line,
+            message: format!("unrecognized key name `{other}`"),
+        }),
+    }
+}
+
+/// Whether an action is held, and how many press/release edges it crossed
+/// since the previous sample.
+#[derive(Clone, Copy, Default)]
+pub struct ActionState {
+    pub is_down: bool,
+    pub half_transition_count: u32,
+}
+
+impl ActionState {
+    pub fn pressed(self) -> bool {
+        self.is_down && self.half_transition_count > 0
+    }
+}
+
+/// A single frame's sampled input, derived from an [`InputMap`].
+#[derive(Default)]
+pub struct InputState {
+    actions: HashMap<GameAction, ActionState>,
+}
+
+impl InputState {
+    pub fn down(&self, action: GameAction) -> bool {
+        self.actions.get(&action).is_some_and(|a| a.is_down)
+    }
+
+    pub fn pressed(&self, action: GameAction) -> bool {
+        self.actions.get(&action).is_some_and(|a| a.pressed())
+    }
+
+    /// Samples raylib's current key state, deriving transition counts by
+    /// diffing against the previous frame's `InputState`.
+    pub fn capture(rl: &RaylibHandle, map: &InputMap, prev: &InputState) -> InputState {
+        let mut actions = HashMap::new();
+        for action in GameAction::ALL {
+            let is_down = map.keys_for(action).iter().any(|&key| rl.is_key_down(key));
+            let was_down = prev.down(action);
+            actions.insert(
+                action,
+                ActionState {
+                    is_down,
+                    half_transition_count: u32::from(is_down != was_down),
+                },
+            );
+        }
+        InputState { actions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_rebound_key_and_sensitivity_over_the_defaults() {
+        let map = InputMap::parse("jump: LEFT_SHIFT\nmouse_sensitivity: 0.02\n").unwrap();
+        assert_eq!(map.keys_for(GameAction::Jump), [KeyboardKey::KEY_LEFT_SHIFT]);
+        assert_eq!(map.mouse_sensitivity, 0.02);
+        // Untouched bindings keep the defaults.
+        assert_eq!(map.keys_for(GameAction::MoveForward), [KeyboardKey::KEY_W]);
+    }
+
+    #[test]
+    fn parses_multiple_keys_bound_to_one_action() {
+        let map = InputMap::parse("move_forward: W, UP\n").unwrap();
+        assert_eq!(
+            map.keys_for(GameAction::MoveForward),
+            [KeyboardKey::KEY_W, KeyboardKey::KEY_UP]
+        );
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_a_malformed_line() {
+        let err = InputMap::parse("jump: SPACE\nnonsense\n").unwrap_err();
+        match err {
+            InputError::Parse { line, .. } => assert_eq!(line, 2),
+            InputError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_an_unknown_action() {
+        let err = InputMap::parse("jump: SPACE\nbogus: W\n").unwrap_err();
+        match err {
+            InputError::Parse { line, .. } => assert_eq!(line, 2),
+            InputError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_an_invalid_mouse_sensitivity() {
+        let err = InputMap::parse("jump: SPACE\nmouse_sensitivity: fast\n").unwrap_err();
+        match err {
+            InputError::Parse { line, .. } => assert_eq!(line, 2),
+            InputError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_an_unrecognized_key_name() {
+        let err = InputMap::parse("jump: SPACE\nmove_forward: W, XYZZY\n").unwrap_err();
+        match err {
+            InputError::Parse { line, .. } => assert_eq!(line, 2),
+            InputError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+}
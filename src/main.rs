@@ -1,9 +1,14 @@
 mod engine;
+mod input;
 mod map;
+mod netcode;
 mod player;
 mod raycaster;
+mod sim;
+mod sprite;
 
 use engine::run_game;
+use netcode::NetcodeConfig;
 use raylib::prelude::*;
 
 fn main() {
@@ -13,5 +18,14 @@ fn main() {
         .vsync()
         .build();
     rl.set_target_fps(60);
-    run_game(&mut rl, &thread);
+
+    // `thurs <local_player> <bind_addr> <peer_addr> [input_delay]` starts a
+    // two-player rollback session; with no args the game runs solo.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let netcode = NetcodeConfig::from_args(&args);
+
+    if let Err(err) = run_game(&mut rl, &thread, netcode) {
+        eprintln!("fatal: {err}");
+        std::process::exit(1);
+    }
 }
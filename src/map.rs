@@ -0,0 +1,278 @@
+use raylib::color::Color;
+use raylib::math::Vector2;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A runtime level loaded from a plain-text map file.
+///
+/// Tiles are stored row-major, `tiles[y * width + x]`. A value of `0` is
+/// open space; any non-zero value is a wall *type id*. The renderer uses it
+/// to look up the wall's height profile (see [`Map::wall_profile`]); every
+/// wall type is drawn with the same texture.
+pub struct Map {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<u8>,
+    /// Per-cell floor/ceiling texture id, selecting into the engine's
+    /// floor/ceiling texture arrays so rooms can use different ground
+    /// and roof looks. Defaults to all-`0` when a map omits the `floors:`
+    /// block.
+    pub floor_tiles: Vec<u8>,
+    pub spawn: Vector2,
+    pub spawn_dir: Vector2,
+    pub ceiling_color: Color,
+    pub floor_color: Color,
+}
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "failed to read map file: {err}"),
+            MapError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl From<std::io::Error> for MapError {
+    fn from(err: std::io::Error) -> Self {
+        MapError::Io(err)
+    }
+}
+
+impl Map {
+    pub fn tile(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return 0;
+        }
+        self.tiles[y as usize * self.width + x as usize]
+    }
+
+    pub fn floor_tile(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return 0;
+        }
+        self.floor_tiles[y as usize * self.width + x as usize]
+    }
+
+    /// Returns `(height_fraction, vertical_offset)` for a wall type id, both
+    /// expressed as a fraction of a full floor-to-ceiling wall. `offset` is
+    /// measured up from the floor, so `(0.5, 0.0)` is a waist-high barrier
+    /// and `(0.3, 0.35)` is a window slit floating mid-wall.
+    pub fn wall_profile(tile: u8) -> (f32, f32) {
+        match tile {
+            2 => (0.5, 0.0),
+            3 => (0.3, 0.35),
+            4 => (0.6, 0.4),
+            _ => (1.0, 0.0),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapError> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parses a header block of `key: value` lines, a blank line, then a
+    /// block of tile-glyph rows.
+    pub fn parse(text: &str) -> Result<Self, MapError> {
+        let mut name = String::new();
+        let mut spawn = Vector2::new(1.5, 1.5);
+        let mut spawn_dir = Vector2::new(-1.0, 0.0);
+        let mut ceiling_color = Color::new(20, 20, 30, 255);
+        let mut floor_color = Color::new(40, 30, 20, 255);
+
+        let mut lines = text.lines().enumerate().peekable();
+        while let Some(&(_, line)) = lines.peek() {
+            if line.trim().is_empty() {
+                lines.next();
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                break;
+            };
+            let (line_no, _) = lines.next().unwrap();
+            let key = key.trim();
+            let value = value.trim();
+
+            match key {
+                "name" => name = value.to_string(),
+                "spawn" => spawn = parse_vector2(value, line_no + 1)?,
+                "spawn_dir" => spawn_dir = parse_vector2(value, line_no + 1)?,
+                "ceiling" => ceiling_color = parse_color(value, line_no + 1)?,
+                "floor" => floor_color = parse_color(value, line_no + 1)?,
+                "rows" => break,
+                other => {
+                    return Err(MapError::Parse {
+                        line: line_no + 1,
+                        message: format!("unknown header key `{other}`"),
+                    })
+                }
+            }
+        }
+
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        let mut row_lines: Vec<usize> = Vec::new();
+        let mut floor_rows: Vec<Vec<u8>> = Vec::new();
+        let mut in_floors = false;
+        for (line_no, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.trim() == "floors:" {
+                in_floors = true;
+                continue;
+            }
+            let row = parse_tile_row(line, line_no + 1)?;
+            if in_floors {
+                floor_rows.push(row);
+            } else {
+                rows.push(row);
+                row_lines.push(line_no + 1);
+            }
+        }
+
+        if rows.is_empty() {
+            return Err(MapError::Parse {
+                line: text.lines().count() + 1,
+                message: "map has no rows".to_string(),
+            });
+        }
+
+        let width = rows[0].len();
+        for (row, &line) in rows.iter().zip(&row_lines) {
+            if row.len() != width {
+                return Err(MapError::Parse {
+                    line,
+                    message: format!("row has {} tiles, expected {width}", row.len()),
+                });
+            }
+        }
+
+        let height = rows.len();
+        let tiles = rows.into_iter().flatten().collect();
+
+        let floor_tiles = if floor_rows.is_empty() {
+            vec![0; width * height]
+        } else {
+            if floor_rows.len() != height || floor_rows.iter().any(|row| row.len() != width) {
+                return Err(MapError::Parse {
+                    line: text.lines().count() + 1,
+                    message: "floors: grid must match the rows: grid dimensions".to_string(),
+                });
+            }
+            floor_rows.into_iter().flatten().collect()
+        };
+
+        Ok(Map {
+            name,
+            width,
+            height,
+            tiles,
+            floor_tiles,
+            spawn,
+            spawn_dir,
+            ceiling_color,
+            floor_color,
+        })
+    }
+}
+
+fn parse_tile_row(line: &str, line_no: usize) -> Result<Vec<u8>, MapError> {
+    line.chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(|ch| {
+            ch.to_digit(10).map(|d| d as u8).ok_or_else(|| MapError::Parse {
+                line: line_no,
+                message: format!("invalid tile glyph `{ch}`"),
+            })
+        })
+        .collect()
+}
+
+fn parse_vector2(value: &str, line: usize) -> Result<Vector2, MapError> {
+    let mut parts = value.split_whitespace();
+    let (Some(x), Some(y), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(MapError::Parse {
+            line,
+            message: format!("expected `x y`, got `{value}`"),
+        });
+    };
+    let parse = |s: &str| {
+        s.parse::<f32>().map_err(|_| MapError::Parse {
+            line,
+            message: format!("invalid number `{s}`"),
+        })
+    };
+    Ok(Vector2::new(parse(x)?, parse(y)?))
+}
+
+fn parse_color(value: &str, line: usize) -> Result<Color, MapError> {
+    let mut parts = value.split_whitespace();
+    let (Some(r), Some(g), Some(b), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(MapError::Parse {
+            line,
+            message: format!("expected `r g b`, got `{value}`"),
+        });
+    };
+    let parse = |s: &str| {
+        s.parse::<u8>().map_err(|_| MapError::Parse {
+            line,
+            message: format!("invalid color channel `{s}`"),
+        })
+    };
+    Ok(Color::new(parse(r)?, parse(g)?, parse(b)?, 255))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_header_and_rows() {
+        let map = Map::parse("name: Test\nspawn: 1.5 2.5\nspawn_dir: -1 0\n0 1 0\n0 0 0\n").unwrap();
+        assert_eq!(map.name, "Test");
+        assert_eq!(map.width, 3);
+        assert_eq!(map.height, 2);
+        assert_eq!(map.tile(1, 0), 1);
+        assert_eq!(map.spawn, Vector2::new(1.5, 2.5));
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_a_ragged_row() {
+        let err = Map::parse("0 0 0\n0 0\n").unwrap_err();
+        match err {
+            MapError::Parse { line, .. } => assert_eq!(line, 2),
+            MapError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_an_invalid_tile_glyph() {
+        let err = Map::parse("0 x 0\n0 0 0\n").unwrap_err();
+        match err {
+            MapError::Parse { line, .. } => assert_eq!(line, 1),
+            MapError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn reports_the_offending_line_for_an_unknown_header_key() {
+        let err = Map::parse("bogus: 1\n0 0 0\n").unwrap_err();
+        match err {
+            MapError::Parse { line, .. } => assert_eq!(line, 1),
+            MapError::Io(_) => panic!("expected a parse error"),
+        }
+    }
+}
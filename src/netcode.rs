@@ -0,0 +1,316 @@
+//! Rollback netcode: predict the remote player's input as "last input
+//! seen", keep a ring buffer of recent snapshots, and resimulate forward
+//! from the earliest tick whose prediction turns out to be wrong.
+
+use crate::map::Map;
+use crate::sim::{step, GameState, PlayerInput};
+use std::collections::VecDeque;
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// How many ticks we'll predict ahead of the last confirmed remote input
+/// before we'd rather stall than guess further.
+pub const MAX_PREDICTION_WINDOW: usize = 8;
+
+/// Command-line configuration for a two-player session: `<local_player>
+/// <bind_addr> <peer_addr> [input_delay]`. Absent args mean solo play.
+pub struct NetcodeConfig {
+    pub local_player: usize,
+    pub bind_addr: String,
+    pub peer_addr: String,
+    pub input_delay: u32,
+}
+
+impl NetcodeConfig {
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let (local_player, bind_addr, peer_addr, rest) = match args {
+            [local_player, bind_addr, peer_addr, rest @ ..] => {
+                (local_player, bind_addr, peer_addr, rest)
+            }
+            _ => return None,
+        };
+
+        let local_player: usize = local_player.parse().ok()?;
+        if local_player > 1 {
+            return None;
+        }
+
+        Some(NetcodeConfig {
+            local_player,
+            bind_addr: bind_addr.clone(),
+            peer_addr: peer_addr.clone(),
+            input_delay: rest.first().and_then(|s| s.parse().ok()).unwrap_or(2),
+        })
+    }
+}
+
+struct Snapshot {
+    tick: u64,
+    state: GameState,
+    /// Inputs for every player actually used to produce `state` from the
+    /// previous tick (a mix of confirmed and, at the time, predicted).
+    inputs: Vec<PlayerInput>,
+}
+
+/// Drives a deterministic two-player [`GameState`] over UDP with
+/// predict/save/rollback/resimulate rollback.
+pub struct RollbackSession {
+    socket: UdpSocket,
+    local_player: usize,
+    remote_player: usize,
+    /// Ticks of artificial delay added to locally-sent input, trading
+    /// input latency for fewer rollbacks.
+    input_delay: u32,
+    /// Local inputs captured but not yet old enough to apply: `advance`
+    /// pushes the freshly-captured input here and pops the one from
+    /// `input_delay` ticks ago to actually simulate and send, so the same
+    /// delayed input reaches the local sim and the wire at the same tick.
+    pending_local_inputs: VecDeque<PlayerInput>,
+
+    snapshots: VecDeque<Snapshot>,
+    local_inputs: VecDeque<PlayerInput>,
+    /// Remote inputs, confirmed where known and repeated-last where
+    /// still predicted. `remote_inputs[i]` (like `local_inputs[i]`) is the
+    /// input that produced `snapshots[i + 1]`.
+    remote_inputs: VecDeque<PlayerInput>,
+    last_confirmed_remote_input: PlayerInput,
+    confirmed_remote_tick: u64,
+}
+
+impl RollbackSession {
+    pub fn new(
+        bind_addr: impl ToSocketAddrs,
+        peer_addr: impl ToSocketAddrs,
+        local_player: usize,
+        remote_player: usize,
+        input_delay: u32,
+        initial_state: GameState,
+    ) -> io::Result<Self> {
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.connect(peer_addr)?;
+        socket.set_nonblocking(true)?;
+
+        let tick = initial_state.tick;
+        let mut snapshots = VecDeque::with_capacity(MAX_PREDICTION_WINDOW + 1);
+        snapshots.push_back(Snapshot {
+            tick,
+            state: initial_state,
+            inputs: vec![PlayerInput::default(); 2],
+        });
+
+        Ok(RollbackSession {
+            socket,
+            local_player,
+            remote_player,
+            input_delay,
+            pending_local_inputs: VecDeque::new(),
+            snapshots,
+            local_inputs: VecDeque::new(),
+            remote_inputs: VecDeque::new(),
+            last_confirmed_remote_input: PlayerInput::default(),
+            confirmed_remote_tick: tick,
+        })
+    }
+
+    pub fn current_state(&self) -> &GameState {
+        &self.snapshots.back().expect("snapshots is never empty").state
+    }
+
+    /// Predicts forward by one tick using the local input from
+    /// `input_delay` ticks ago (not `local_input` itself — see
+    /// `pending_local_inputs`) and the last confirmed (or still-predicted)
+    /// remote input, saving a snapshot so this tick can be corrected later
+    /// if the real remote input differs. Stalls (returns the current state
+    /// unchanged and drops `local_input`) once the current tick is
+    /// `MAX_PREDICTION_WINDOW` ticks past the last confirmed remote input.
+    pub fn advance(&mut self, local_input: PlayerInput, map: &Map) -> &GameState {
+        if self.current_state().tick >= self.confirmed_remote_tick + MAX_PREDICTION_WINDOW as u64 {
+            return self.current_state();
+        }
+        self.pending_local_inputs.push_back(local_input);
+        let delayed_input = if self.pending_local_inputs.len() > self.input_delay as usize {
+            self.pending_local_inputs.pop_front().unwrap()
+        } else {
+            PlayerInput::default()
+        };
+
+        let mut inputs = vec![PlayerInput::default(); 2];
+        inputs[self.local_player] = delayed_input;
+        inputs[self.remote_player] = self
+            .remote_inputs
+            .back()
+            .copied()
+            .unwrap_or(self.last_confirmed_remote_input);
+
+        let prev = &self.snapshots.back().expect("snapshots is never empty").state;
+        let next_state = step(prev, &inputs, map);
+        let tick = next_state.tick;
+
+        self.send_local_input(tick, delayed_input);
+
+        self.local_inputs.push_back(delayed_input);
+        self.remote_inputs.push_back(inputs[self.remote_player]);
+        self.snapshots.push_back(Snapshot {
+            tick,
+            state: next_state,
+            inputs,
+        });
+
+        while self.snapshots.len() > MAX_PREDICTION_WINDOW + 1 {
+            self.snapshots.pop_front();
+            self.local_inputs.pop_front();
+            self.remote_inputs.pop_front();
+        }
+
+        self.current_state()
+    }
+
+    /// Polls the socket for remote input packets, applying a rollback +
+    /// resimulate pass whenever a confirmed input disagrees with what we
+    /// predicted for that tick.
+    pub fn receive(&mut self, map: &Map) -> io::Result<()> {
+        let mut buf = [0u8; 11];
+        loop {
+            match self.socket.recv(&mut buf) {
+                Ok(_) => self.on_remote_packet(&buf, map),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn on_remote_packet(&mut self, buf: &[u8; 11], map: &Map) {
+        let tick = u64::from_le_bytes(buf[0..8].try_into().unwrap());
+        let input = PlayerInput::from_bytes([buf[8], buf[9], buf[10]]);
+        self.on_remote_input(tick, input, map);
+    }
+
+    fn on_remote_input(&mut self, tick: u64, input: PlayerInput, map: &Map) {
+        if tick <= self.confirmed_remote_tick {
+            return;
+        }
+        self.confirmed_remote_tick = tick;
+        self.last_confirmed_remote_input = input;
+
+        // `snapshots[0]` is the tick-before-any-buffered-input baseline, so
+        // the input that produced `snapshots[i + 1]` (tick `oldest_tick +
+        // i + 1`) lives at `remote_inputs[i]`.
+        let Some(oldest_tick) = self.snapshots.front().map(|s| s.tick) else {
+            return;
+        };
+        let Some(index) = tick
+            .checked_sub(oldest_tick)
+            .and_then(|d| d.checked_sub(1))
+            .map(|d| d as usize)
+        else {
+            return;
+        };
+        if index >= self.remote_inputs.len() || self.remote_inputs[index] == input {
+            return;
+        }
+
+        self.remote_inputs[index] = input;
+        self.rollback_and_resimulate_from(index, map);
+    }
+
+    /// Restores the snapshot immediately before `index`, then replays every
+    /// tick from there forward with the corrected input history.
+    fn rollback_and_resimulate_from(&mut self, index: usize, map: &Map) {
+        let mut state = self.snapshots[index].state.clone();
+
+        for i in index..self.local_inputs.len() {
+            let mut inputs = vec![PlayerInput::default(); 2];
+            inputs[self.local_player] = self.local_inputs[i];
+            inputs[self.remote_player] = self.remote_inputs[i];
+
+            state = step(&state, &inputs, map);
+            // Invariant: snapshots always holds one more entry than the
+            // input histories (the extra being the initial/tick-0 state),
+            // so tick `i`'s resulting state lives at `snapshots[i + 1]`.
+            self.snapshots[i + 1] = Snapshot {
+                tick: state.tick,
+                state: state.clone(),
+                inputs,
+            };
+        }
+    }
+
+    /// Sends `input` tagged with the tick it's actually being simulated
+    /// for, so the remote peer's rollback check compares it against the
+    /// same tick we just applied it to locally.
+    fn send_local_input(&mut self, tick: u64, input: PlayerInput) {
+        let mut packet = [0u8; 11];
+        packet[0..8].copy_from_slice(&tick.to_le_bytes());
+        packet[8..11].copy_from_slice(&input.to_bytes());
+        let _ = self.socket.send(&packet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::PlayerState;
+    use raylib::math::Vector2;
+    use std::thread;
+    use std::time::Duration;
+
+    fn open_map() -> Map {
+        Map::parse("0 0 0 0\n0 0 0 0\n0 0 0 0\n0 0 0 0\n").unwrap()
+    }
+
+    fn initial_state() -> GameState {
+        GameState::new(vec![
+            PlayerState::new(Vector2::new(1.5, 1.5), Vector2::new(1.0, 0.0), Vector2::new(0.0, 0.5)),
+            PlayerState::new(Vector2::new(2.5, 2.5), Vector2::new(-1.0, 0.0), Vector2::new(0.0, 0.5)),
+        ])
+    }
+
+    /// Player 1 stays idle for a few ticks, then starts moving forward —
+    /// late enough that the other session will already have predicted a
+    /// couple of ticks of "still idle" before the real input lands,
+    /// forcing at least one rollback + resimulate pass.
+    fn player_one_input(tick: usize) -> PlayerInput {
+        if tick < 3 {
+            PlayerInput::default()
+        } else {
+            PlayerInput::with_turn(PlayerInput::FORWARD, 0.0)
+        }
+    }
+
+    #[test]
+    fn rollback_reproduces_the_same_state_as_direct_forward_simulation() {
+        let map = open_map();
+        const TICKS: usize = 8;
+
+        let mut a =
+            RollbackSession::new("127.0.0.1:58231", "127.0.0.1:58232", 0, 1, 0, initial_state())
+                .unwrap();
+        let mut b =
+            RollbackSession::new("127.0.0.1:58232", "127.0.0.1:58231", 1, 0, 0, initial_state())
+                .unwrap();
+
+        for tick in 0..TICKS {
+            a.receive(&map).unwrap();
+            b.receive(&map).unwrap();
+            a.advance(PlayerInput::default(), &map);
+            b.advance(player_one_input(tick), &map);
+            thread::sleep(Duration::from_millis(5));
+        }
+        // Drain whatever packets are still in flight so both sides settle
+        // on the same corrected history before comparing.
+        for _ in 0..5 {
+            a.receive(&map).unwrap();
+            b.receive(&map).unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let mut reference = initial_state();
+        for tick in 0..TICKS {
+            let inputs = [PlayerInput::default(), player_one_input(tick)];
+            reference = step(&reference, &inputs, &map);
+        }
+
+        assert_eq!(a.current_state(), &reference);
+        assert_eq!(b.current_state(), &reference);
+    }
+}
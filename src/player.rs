@@ -4,8 +4,12 @@ pub struct Player {
     pub pos: Vector2,
     pub dir: Vector2,
     pub plane: Vector2,
-    pub move_speed: f32,
-    pub rot_speed: f32,
+    /// Vertical look offset, in screen pixels (not radians) so the
+    /// renderer can add it straight into `draw_start`/`draw_end` math.
+    pub pitch: f32,
+    /// Camera height above the nominal eye level, in world units.
+    pub pos_z: f32,
+    pub vel_z: f32,
 }
 
 impl Player {
@@ -18,23 +22,13 @@ impl Player {
             pos: Vector2::new(3.5, 3.5),
             dir,
             plane,
-            move_speed: 0.05,
-            rot_speed: 0.03,
+            pitch: 0.0,
+            pos_z: 0.0,
+            vel_z: 0.0,
         }
     }
-    pub fn rotate(&mut self, angle: f32) {
-        let cos = angle.cos();
-        let sin = angle.sin();
 
-        let old_dir = self.dir;
-        self.dir.x = old_dir.x * cos - old_dir.y * sin;
-        self.dir.y = old_dir.x * sin + old_dir.y * cos;
-
-        let old_plane = self.plane;
-        self.plane.x = old_plane.x * cos - old_plane.y * sin;
-        self.plane.y = old_plane.x * sin + old_plane.y * cos;
-
-        self.dir = self.dir.normalized();
-        self.plane = self.plane.normalized();
+    pub fn is_grounded(&self) -> bool {
+        self.pos_z <= 0.0
     }
 }
@@ -1,4 +1,4 @@
-use crate::map::{MAP_HEIGHT, MAP_WIDTH, WORLD_MAP};
+use crate::map::Map;
 use raylib::math::Vector2;
 
 #[derive(Debug, Clone, Copy)]
@@ -20,9 +20,10 @@ pub struct RayHit {
     pub map_pos: IVec2,
     pub side: usize,
     pub step: IVec2,
+    pub tile: u8,
 }
 
-pub fn cast_ray(origin: Vector2, dir: Vector2) -> Option<RayHit> {
+pub fn cast_ray(map: &Map, origin: Vector2, dir: Vector2) -> Option<RayHit> {
     let mut map_pos = IVec2::new(origin.x as i32, origin.y as i32);
     let delta_dist = Vector2::new(
         if dir.x != 0.0 {
@@ -59,11 +60,11 @@ pub fn cast_ray(origin: Vector2, dir: Vector2) -> Option<RayHit> {
             let next_x = map_pos.x + step_x;
             let next_y = map_pos.y + step_y;
             let hit_x = next_x >= 0
-                && next_x < MAP_WIDTH as i32
-                && WORLD_MAP[map_pos.y as usize][next_x as usize] > 0;
+                && next_x < map.width as i32
+                && map.tile(next_x, map_pos.y) > 0;
             let hit_y = next_y >= 0
-                && next_y < MAP_HEIGHT as i32
-                && WORLD_MAP[next_y as usize][map_pos.x as usize] > 0;
+                && next_y < map.height as i32
+                && map.tile(map_pos.x, next_y) > 0;
 
             if hit_x || hit_y {
                 if hit_x && hit_y {
@@ -89,13 +90,14 @@ pub fn cast_ray(origin: Vector2, dir: Vector2) -> Option<RayHit> {
 
         if map_pos.x < 0
             || map_pos.y < 0
-            || map_pos.x as usize >= MAP_WIDTH
-            || map_pos.y as usize >= MAP_HEIGHT
+            || map_pos.x as usize >= map.width
+            || map_pos.y as usize >= map.height
         {
             return None;
         }
 
-        if WORLD_MAP[map_pos.y as usize][map_pos.x as usize] > 0 {
+        let tile = map.tile(map_pos.x, map_pos.y);
+        if tile > 0 {
             let distance = if side == 0 {
                 side_dist_x - delta_dist.x
             } else {
@@ -109,6 +111,7 @@ pub fn cast_ray(origin: Vector2, dir: Vector2) -> Option<RayHit> {
                 map_pos,
                 side,
                 step,
+                tile,
             });
         }
     }
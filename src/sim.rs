@@ -0,0 +1,242 @@
+use crate::map::Map;
+use raylib::math::{Rectangle, Vector2};
+
+/// Nominal ticks per second of the simulation. `engine::run_game` calls
+/// [`step`] once per rendered frame with the frame rate capped at 60, so the
+/// tick rate follows the frame rate: elapsed real time is not accumulated,
+/// and a slow frame slows the simulation down with it.
+pub const TICK_HZ: u32 = 60;
+
+/// One player's input for a single tick, serialized small enough to send
+/// over UDP: a button bitmask plus a quantized turn delta. No floats are
+/// read from raylib inside [`step`] — everything it needs arrives here.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayerInput {
+    pub buttons: u8,
+    /// Turn delta for this tick, quantized to `1 / TURN_QUANTIZE` radians
+    /// so the same i16 always produces the same rotation on every peer.
+    pub turn: i16,
+}
+
+impl PlayerInput {
+    pub const FORWARD: u8 = 1 << 0;
+    pub const BACK: u8 = 1 << 1;
+    pub const STRAFE_LEFT: u8 = 1 << 2;
+    pub const STRAFE_RIGHT: u8 = 1 << 3;
+    pub const JUMP: u8 = 1 << 4;
+
+    const TURN_QUANTIZE: f32 = 10_000.0;
+
+    pub fn with_turn(buttons: u8, turn_radians: f32) -> Self {
+        PlayerInput {
+            buttons,
+            turn: (turn_radians * Self::TURN_QUANTIZE).round() as i16,
+        }
+    }
+
+    pub fn turn_radians(self) -> f32 {
+        self.turn as f32 / Self::TURN_QUANTIZE
+    }
+
+    pub fn down(self, button: u8) -> bool {
+        self.buttons & button != 0
+    }
+
+    pub fn to_bytes(self) -> [u8; 3] {
+        let turn = self.turn.to_le_bytes();
+        [self.buttons, turn[0], turn[1]]
+    }
+
+    pub fn from_bytes(bytes: [u8; 3]) -> Self {
+        PlayerInput {
+            buttons: bytes[0],
+            turn: i16::from_le_bytes([bytes[1], bytes[2]]),
+        }
+    }
+}
+
+/// One player's simulated pose. Deliberately a subset of `Player`: only
+/// the fields that must agree bit-for-bit across peers. Local-only
+/// presentation state (pitch, jump height) stays outside the networked
+/// state and is layered on top when rendering.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayerState {
+    pub pos: Vector2,
+    pub dir: Vector2,
+    pub plane: Vector2,
+}
+
+impl PlayerState {
+    pub fn new(pos: Vector2, dir: Vector2, plane: Vector2) -> Self {
+        PlayerState { pos, dir, plane }
+    }
+}
+
+/// The full deterministic simulation state for every connected player at
+/// one tick. Cheap to clone, which is what makes rollback snapshots
+/// affordable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameState {
+    pub tick: u64,
+    pub players: Vec<PlayerState>,
+}
+
+impl GameState {
+    pub fn new(players: Vec<PlayerState>) -> Self {
+        GameState { tick: 0, players }
+    }
+}
+
+const MOVE_SPEED: f32 = 0.05;
+const COLLISION_RADIUS: f32 = 0.1;
+
+fn rotate(dir: Vector2, angle: f32) -> Vector2 {
+    let cos = angle.cos();
+    let sin = angle.sin();
+    Vector2::new(dir.x * cos - dir.y * sin, dir.x * sin + dir.y * cos).normalized()
+}
+
+fn collision_check(circle_pos: Vector2, radius: f32, rect: Rectangle) -> bool {
+    let closest_x = circle_pos.x.clamp(rect.x, rect.x + rect.width);
+    let closest_y = circle_pos.y.clamp(rect.y, rect.y + rect.height);
+
+    let dx = circle_pos.x - closest_x;
+    let dy = circle_pos.y - closest_y;
+
+    dx * dx + dy * dy < radius * radius
+}
+
+fn is_colliding(map: &Map, pos: Vector2, radius: f32) -> bool {
+    (0..map.height).any(|y| {
+        (0..map.width).any(|x| {
+            map.tile(x as i32, y as i32) != 0
+                && collision_check(pos, radius, Rectangle::new(x as f32, y as f32, 1.0, 1.0))
+        })
+    })
+}
+
+/// Moves `pos` by `delta`, sliding along whichever axis is still open when
+/// the full move would clip a wall. Mirrors what `run_game` used to do
+/// inline before movement became part of the deterministic step.
+fn move_with_sliding(map: &Map, pos: Vector2, delta: Vector2) -> Vector2 {
+    let try_pos = pos + delta;
+    if !is_colliding(map, try_pos, COLLISION_RADIUS) {
+        return try_pos;
+    }
+
+    let mut next = pos;
+    let x_only = Vector2::new(try_pos.x, pos.y);
+    let y_only = Vector2::new(pos.x, try_pos.y);
+    if !is_colliding(map, x_only, COLLISION_RADIUS) {
+        next.x = x_only.x;
+    }
+    if !is_colliding(map, y_only, COLLISION_RADIUS) {
+        next.y = y_only.y;
+    }
+    next
+}
+
+/// Advances `state` by one tick using only `inputs` and `map` — no
+/// wall-clock time, no device polling. Same `state` + `inputs` + `map`
+/// always produces the same result, which is the entire precondition for
+/// rollback netcode: a peer can replay ticks from a saved snapshot and get
+/// back the state it would have had if it had known the real inputs all
+/// along.
+pub fn step(state: &GameState, inputs: &[PlayerInput], map: &Map) -> GameState {
+    let mut next = state.clone();
+    next.tick += 1;
+
+    for (player, &input) in next.players.iter_mut().zip(inputs) {
+        player.dir = rotate(player.dir, input.turn_radians());
+        player.plane = rotate(player.plane, input.turn_radians());
+
+        let forward = player.dir * MOVE_SPEED;
+        let strafe = Vector2::new(-player.dir.y, player.dir.x) * MOVE_SPEED;
+
+        if input.down(PlayerInput::FORWARD) {
+            player.pos = move_with_sliding(map, player.pos, forward);
+        }
+        if input.down(PlayerInput::BACK) {
+            player.pos = move_with_sliding(map, player.pos, -forward);
+        }
+        if input.down(PlayerInput::STRAFE_LEFT) {
+            player.pos = move_with_sliding(map, player.pos, strafe);
+        }
+        if input.down(PlayerInput::STRAFE_RIGHT) {
+            player.pos = move_with_sliding(map, player.pos, -strafe);
+        }
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_map() -> Map {
+        Map::parse("0 0 0 0\n0 0 0 0\n0 0 0 0\n0 0 0 0\n").unwrap()
+    }
+
+    #[test]
+    fn same_state_and_inputs_always_produce_the_same_result() {
+        let map = open_map();
+        let state = GameState::new(vec![PlayerState::new(
+            Vector2::new(1.5, 1.5),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(0.0, 0.5),
+        )]);
+        let inputs = [PlayerInput::with_turn(PlayerInput::FORWARD, 0.1)];
+
+        let a = step(&state, &inputs, &map);
+        let b = step(&state, &inputs, &map);
+
+        assert_eq!(a, b);
+        assert_eq!(a.tick, 1);
+    }
+
+    #[test]
+    fn replaying_the_same_input_history_reaches_the_same_state() {
+        let map = open_map();
+        let state = GameState::new(vec![PlayerState::new(
+            Vector2::new(1.5, 1.5),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(0.0, 0.5),
+        )]);
+        let history = [
+            [PlayerInput::with_turn(PlayerInput::FORWARD, 0.0)],
+            [PlayerInput::with_turn(PlayerInput::FORWARD, 0.2)],
+            [PlayerInput::with_turn(PlayerInput::STRAFE_RIGHT, -0.1)],
+        ];
+
+        let mut first_pass = state.clone();
+        for inputs in &history {
+            first_pass = step(&first_pass, inputs, &map);
+        }
+
+        let mut replayed = state;
+        for inputs in &history {
+            replayed = step(&replayed, inputs, &map);
+        }
+
+        assert_eq!(first_pass, replayed);
+    }
+
+    #[test]
+    fn forward_movement_into_a_wall_stops_at_the_wall() {
+        let map = Map::parse("0 0 0\n0 0 1\n0 0 0\n").unwrap();
+        let state = GameState::new(vec![PlayerState::new(
+            Vector2::new(1.2, 1.5),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(0.0, 0.5),
+        )]);
+        let inputs = [PlayerInput::with_turn(PlayerInput::FORWARD, 0.0)];
+
+        let mut next = state;
+        for _ in 0..50 {
+            next = step(&next, &inputs, &map);
+        }
+
+        assert!(next.players[0].pos.x < 2.0 - COLLISION_RADIUS);
+    }
+}
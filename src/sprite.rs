@@ -0,0 +1,112 @@
+use crate::player::Player;
+use raylib::prelude::*;
+
+/// A billboarded world object: a flat, camera-facing texture anchored at
+/// a world-space position. Used for enemies, items, and decoration.
+pub struct Sprite<'a> {
+    pub pos: Vector2,
+    pub texture: &'a Texture2D,
+}
+
+/// Draws `sprites` back-to-front. `wall_bounds[x]` is the `(top, bottom)`
+/// screen-space slice the wall raycaster actually drew in column `x` (which
+/// may be shorter than the full wall for barriers/window slits); `zbuffer`
+/// is that column's corrected wall distance. A sprite is only occluded
+/// where a column's drawn wall slice is both nearer than it and actually
+/// overlaps its row — the parts of the column above/below a short wall
+/// still show the sprite.
+pub fn draw_sprites(
+    d: &mut RaylibDrawHandle,
+    player: &Player,
+    sprites: &[Sprite],
+    zbuffer: &[f32],
+    wall_bounds: &[(f32, f32)],
+    horizon: f32,
+    screen_w: i32,
+    screen_h: i32,
+) {
+    let mut order: Vec<usize> = (0..sprites.len()).collect();
+    order.sort_by(|&a, &b| {
+        let dist_a = (sprites[a].pos - player.pos).length_sqr();
+        let dist_b = (sprites[b].pos - player.pos).length_sqr();
+        dist_b.partial_cmp(&dist_a).unwrap()
+    });
+
+    let inv_det = 1.0 / (player.plane.x * player.dir.y - player.dir.x * player.plane.y);
+    let projected_plane = screen_w as f32 / 2.0;
+
+    for idx in order {
+        let sprite = &sprites[idx];
+        let rel = sprite.pos - player.pos;
+
+        let transform_x = inv_det * (player.dir.y * rel.x - player.dir.x * rel.y);
+        let transform_y = inv_det * (-player.plane.y * rel.x + player.plane.x * rel.y);
+
+        if transform_y <= 0.0 {
+            continue;
+        }
+
+        let sprite_screen_x = (screen_w as f32 / 2.0) * (1.0 + transform_x / transform_y);
+        let sprite_size = (screen_h as f32 / transform_y).abs();
+        let sprite_horizon = horizon - player.pos_z * projected_plane / transform_y;
+
+        let draw_start_y = (sprite_horizon - sprite_size / 2.0).max(0.0);
+        let draw_end_y = (sprite_horizon + sprite_size / 2.0).min(screen_h as f32);
+        let draw_start_x = (sprite_screen_x - sprite_size / 2.0).max(0.0);
+        let draw_end_x = (sprite_screen_x + sprite_size / 2.0).min(screen_w as f32);
+        let draw_height = draw_end_y - draw_start_y;
+        if draw_height <= 0.0 {
+            continue;
+        }
+
+        for stripe in draw_start_x as i32..draw_end_x as i32 {
+            if stripe < 0 || stripe >= screen_w {
+                continue;
+            }
+
+            let tex_x = ((stripe as f32 - draw_start_x) / sprite_size
+                * sprite.texture.width() as f32) as i32;
+            let tex_x = tex_x.clamp(0, sprite.texture.width() - 1);
+
+            let (wall_start, wall_end) = wall_bounds[stripe as usize];
+            let behind_wall = transform_y >= zbuffer[stripe as usize];
+            let segments = if behind_wall {
+                [
+                    (draw_start_y, draw_end_y.min(wall_start)),
+                    (draw_start_y.max(wall_end), draw_end_y),
+                ]
+            } else {
+                [(draw_start_y, draw_end_y), (draw_end_y, draw_end_y)]
+            };
+
+            for (seg_start, seg_end) in segments {
+                if seg_end <= seg_start {
+                    continue;
+                }
+
+                let v0 = (seg_start - draw_start_y) / draw_height;
+                let v1 = (seg_end - draw_start_y) / draw_height;
+                let tex_h = sprite.texture.height() as f32;
+
+                d.draw_texture_pro(
+                    sprite.texture,
+                    Rectangle {
+                        x: tex_x as f32,
+                        y: v0 * tex_h,
+                        width: 1.0,
+                        height: (v1 - v0) * tex_h,
+                    },
+                    Rectangle {
+                        x: stripe as f32,
+                        y: seg_start,
+                        width: 1.0,
+                        height: seg_end - seg_start,
+                    },
+                    Vector2::zero(),
+                    0.0,
+                    Color::WHITE,
+                );
+            }
+        }
+    }
+}